@@ -4,17 +4,19 @@ use std::{
     thread, time,
 };
 
+use tracing::{info, trace, warn};
+
 pub mod net;
 
 pub fn main() {
-    // log::init().expect("failed to initialize logger");
+    let _ = tracing_subscriber::fmt::try_init();
 
-    // trace!("Hello, Xenotech!");
+    trace!("Hello, Xenotech!");
 
     thread::Builder::new()
         .name("client".to_string())
         .spawn(|| {
-            // trace!("Hello!");
+            trace!("Hello!");
             client();
         })
         .expect("failed to start client");
@@ -51,10 +53,18 @@ fn interface() {
 }
 
 fn start() -> User {
+    loop {
+        if let Some(user) = attempt(prompt()) {
+            return user;
+        }
+    }
+}
+
+fn prompt() -> LoginOption {
     let stdin = io::stdin();
 
-    // info!("Do you have an account?");
-    // info!("Type \"signup\" or \"login\"");
+    info!("Do you have an account?");
+    info!("Type \"signup\" or \"login\"");
 
     let mut input = String::new();
     let login_option;
@@ -70,7 +80,7 @@ fn start() -> User {
                 login_option = LoginOption::Login;
             }
             _ => {
-                // info!("Invalid input: \"{}\"", input);
+                info!("Invalid input: \"{}\"", input);
                 // input = default();
                 continue;
             }
@@ -78,23 +88,13 @@ fn start() -> User {
         break;
     }
 
+    login_option
+}
+
+fn attempt(login_option: LoginOption) -> Option<User> {
     match login_option {
-        LoginOption::Signup => match signup() {
-            Ok(user) => user,
-            Err(e) => {
-                // error!("{:?}", e);
-                println!("");
-                start()
-            }
-        },
-        LoginOption::Login => match login() {
-            Ok(user) => user,
-            Err(e) => {
-                // error!("{:?}", e);
-                println!("");
-                start()
-            }
-        },
+        LoginOption::Signup => signup().map_err(|e| warn!("{:?}", e)).ok(),
+        LoginOption::Login => login().map_err(|e| warn!("{:?}", e)).ok(),
     }
 }
 
@@ -109,3 +109,34 @@ fn signup() -> Result<User, SignupError> {
 fn client() {}
 
 fn server() {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Levels(Arc<Mutex<Vec<Level>>>);
+
+    impl<S: Subscriber> Layer<S> for Levels {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            self.0.lock().unwrap().push(*event.metadata().level());
+        }
+    }
+
+    #[test]
+    fn failed_login_warns() {
+        let levels = Levels::default();
+        let subscriber = tracing_subscriber::registry().with(levels.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(attempt(LoginOption::Login).is_none());
+        });
+
+        assert_eq!(*levels.0.lock().unwrap(), vec![Level::WARN]);
+    }
+}
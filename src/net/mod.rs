@@ -1,3 +1,5 @@
+pub mod udp;
+
 /*use std::net::{ToSocketAddrs, UdpSocket};
 
 use common::net::udp::SocketError;
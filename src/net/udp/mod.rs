@@ -1,9 +1,4 @@
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-    io::Write,
-    mem,
-};
+use std::{io::Write, mem};
 
 use flate2::{
     write::{DeflateDecoder, DeflateEncoder},
@@ -13,7 +8,18 @@ use serde_derive::{Deserialize, Serialize};
 
 pub type PacketId = usize;
 
+/// Leading byte of every encoded packet; bump it whenever the wire format
+/// of `Packet` or any message type changes, including the FNV-1a checksum
+/// that follows it.
+pub const PROTOCOL_VERSION: u8 = 2;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+#[derive(Debug)]
 pub enum PacketError {
+    Truncated,
+    Version,
     Serialize,
     Compress,
     Checksum,
@@ -21,6 +27,7 @@ pub enum PacketError {
     Deserialize,
 }
 
+#[derive(Debug)]
 pub enum SocketError {
     Bind,
     Connect,
@@ -29,7 +36,7 @@ pub enum SocketError {
 
 #[derive(Serialize, Deserialize)]
 pub struct Header {
-    id: PacketId,
+    pub id: PacketId,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -39,13 +46,15 @@ pub struct Packet<M> {
 }
 
 fn checksum_hash(data: &[u8]) -> [u8; 8] {
-    let mut hasher = DefaultHasher::default();
-    data.hash(&mut hasher);
-    hasher.finish().to_be_bytes()
+    data.iter()
+        .fold(FNV_OFFSET_BASIS, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+        .to_be_bytes()
 }
 
 impl<M: serde::ser::Serialize + serde::de::DeserializeOwned> Packet<M> {
-    fn encode(&self) -> Result<Vec<u8>, PacketError> {
+    pub fn encode(&self) -> Result<Vec<u8>, PacketError> {
         let mut data = vec![];
 
         let bytecode = bincode::serialize(self).map_err(|_| PacketError::Serialize)?;
@@ -60,17 +69,25 @@ impl<M: serde::ser::Serialize + serde::de::DeserializeOwned> Packet<M> {
 
         let checksum = checksum_hash(&payload);
 
+        data.push(PROTOCOL_VERSION);
         data.extend(checksum);
         data.extend(payload);
 
         Ok(data)
     }
 
-    fn decode(data: &[u8]) -> Result<Self, PacketError> {
+    pub fn decode(data: &[u8]) -> Result<Self, PacketError> {
         const U64_BYTES: usize = mem::size_of::<u64>();
 
-        let checksum = &data[..U64_BYTES];
-        let payload = &data[U64_BYTES..];
+        let data = match data.split_first() {
+            Some((&PROTOCOL_VERSION, data)) => data,
+            Some(_) => Err(PacketError::Version)?,
+            None => Err(PacketError::Truncated)?,
+        };
+
+        let (checksum, payload) = data
+            .split_at_checked(U64_BYTES)
+            .ok_or(PacketError::Truncated)?;
 
         if checksum != checksum_hash(payload) {
             Err(PacketError::Checksum)?
@@ -79,7 +96,7 @@ impl<M: serde::ser::Serialize + serde::de::DeserializeOwned> Packet<M> {
         let payload = {
             let mut decompressor = DeflateDecoder::new(vec![]);
             decompressor
-                .write_all(&payload)
+                .write_all(payload)
                 .map_err(|_| PacketError::Decompress)?;
             decompressor.finish().map_err(|_| PacketError::Decompress)?
         };
@@ -89,3 +106,73 @@ impl<M: serde::ser::Serialize + serde::de::DeserializeOwned> Packet<M> {
         Ok(this)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet() -> Packet<String> {
+        Packet {
+            header: Header { id: 7 },
+            message: "hello".to_string(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = packet().encode().unwrap();
+        let decoded = Packet::<String>::decode(&data).unwrap();
+
+        assert_eq!(decoded.header.id, 7);
+        assert_eq!(decoded.message, "hello");
+    }
+
+    #[test]
+    fn checksum_is_fnv1a() {
+        assert_eq!(checksum_hash(b""), 0xcbf29ce484222325u64.to_be_bytes());
+        assert_eq!(checksum_hash(b"a"), 0xaf63dc4c8601ec8cu64.to_be_bytes());
+    }
+
+    #[test]
+    fn encoding_is_stable() {
+        // Changing these bytes changes the wire format; bump PROTOCOL_VERSION.
+        let expected = [
+            2, 190, 38, 193, 184, 156, 35, 117, 186, 99, 103, 128, 0, 86, 40, 157, 145, 154, 147,
+            147, 15, 0,
+        ];
+
+        assert_eq!(packet().encode().unwrap(), expected);
+    }
+
+    #[test]
+    fn wrong_version() {
+        let mut data = packet().encode().unwrap();
+        data[0] = PROTOCOL_VERSION.wrapping_add(1);
+
+        assert!(matches!(
+            Packet::<String>::decode(&data),
+            Err(PacketError::Version)
+        ));
+    }
+
+    #[test]
+    fn corrupted_checksum() {
+        let mut data = packet().encode().unwrap();
+        data[1] ^= 0xff;
+
+        assert!(matches!(
+            Packet::<String>::decode(&data),
+            Err(PacketError::Checksum)
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        for data in [&[][..], &[PROTOCOL_VERSION], &[PROTOCOL_VERSION, 0, 0]] {
+            assert!(matches!(
+                Packet::<String>::decode(data),
+                Err(PacketError::Truncated)
+            ));
+        }
+    }
+}